@@ -22,13 +22,15 @@ pub mod pallet {
 	// provide you with lots of traits which provide you with types you will be including in your pallets
 	// frame_support is a package, name of their module is dispatch, which has a trait DispathResultWithPostInfo
 	//
-	use frame_support::{dispatch::DispatchResultWithPostInfo, pallet_prelude::*, traits::{Currency, ReservableCurrency}};
+	use frame_support::{dispatch::{DispatchResult, DispatchResultWithPostInfo}, pallet_prelude::*, traits::{Currency, ExistenceRequirement, Randomness, ReservableCurrency}};
 	// pallet_prelude gives you all different types, i.e. when dealing with blockchain based code always see there is an accountid
 	// every account id could be different on each chain -- it is a generic type which is bounded by various other types (i.e. should not be
-	// greater than length of 16, 32 etc. all of these pre configurations -- if you want to utilize that you would bound your accountId from the 
+	// greater than length of 16, 32 etc. all of these pre configurations -- if you want to utilize that you would bound your accountId from the
 	// pre decided account id types from the prelude)
 	use frame_system::pallet_prelude::*;
+	use sp_core::U256;
 	use sp_runtime::print;
+	use sp_runtime::traits::{CheckedAdd, CheckedSub};
 
 
 	//AccountOf type coming from frame_system
@@ -43,14 +45,25 @@ pub mod pallet {
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 		type Currency: ReservableCurrency<Self::AccountId>;
 
+		// Amount reserved from the caller by set_value when they hold no active deposit, given back by clear_value
+		#[pallet::constant]
+		type ValueDeposit: Get<BalanceOf<Self>>;
+
+		// Cost of a single lottery ticket, reserved from the caller by buy_ticket
+		#[pallet::constant]
+		type TicketPrice: Get<BalanceOf<Self>>;
+		// Source of on-chain randomness used by award_prize to pick a winner
+		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
 
 	}
 
-	#[derive(Encode, Decode, Default, Debug)]
-	pub struct AssetDetails<BalanceOf> {
+	#[derive(Encode, Decode, Debug)]
+	pub struct AssetDetails<AccountId, BalanceOf> {
 		asset_name: Vec<u8>,
 		asset_number: u32,
 		asset_cost: BalanceOf,
+		// Only this account is allowed to deregister the asset and reclaim the deposit
+		asset_owner: AccountId,
 	}
 	// Telling runtime want to incude Event type so look for this type of parameter as well to include
 	#[pallet::event]
@@ -63,6 +76,20 @@ pub mod pallet {
 	pub enum Event<T:Config> {
 		// since we called this T:Config we can now call AccountId which comes from frame_system::Config 
 		HelloValueStored(u32, T::AccountId),
+		// Fired once funds have actually left the sender's free balance and landed in the destination's
+		Transferred(T::AccountId, T::AccountId, BalanceOf<T>),
+		// asset_number of the newly registered asset and the account that registered it
+		AssetRegistered(u32, T::AccountId),
+		// asset_number of the asset that was removed from the registry and the account that removed it
+		AssetDeregistered(u32, T::AccountId),
+		// the caller and the ValueDeposit amount that was just reserved from them
+		ValueReserved(T::AccountId, BalanceOf<T>),
+		// the caller whose ValueDeposit has just been unreserved
+		ValueCleared(T::AccountId),
+		// the caller who just bought a ticket into the current lottery round
+		TicketBought(T::AccountId),
+		// the winning account and the pooled ticket total they were paid
+		PrizeAwarded(T::AccountId, BalanceOf<T>),
 
 	}
 	// this needs to be included in the runtime
@@ -78,6 +105,58 @@ pub mod pallet {
 
 	pub type GetReservedBalance<T> = StorageMap<_, Blake2_128Concat, AccountOf<T>, BalanceOf<T>, ValueQuery>;
 
+	#[pallet::storage]
+	// the amount of ValueDeposit currently held for an account, kept separate from GetReservedBalance
+	// (which also aggregates asset listing deposits and lottery tickets) so set_value/clear_value can
+	// tell whether *this* account has an active ValueDeposit without being fooled by unrelated reserves
+	#[pallet::getter(fn value_deposit_of)]
+	pub type ValueDeposits<T> = StorageMap<_, Blake2_128Concat, AccountOf<T>, BalanceOf<T>, OptionQuery>;
+
+	#[pallet::storage]
+	// the asset registry itself, keyed by the asset_number handed out by NextAssetId
+	#[pallet::getter(fn get_asset_details)]
+	pub type AssetRegistry<T> = StorageMap<_, Blake2_128Concat, u32, AssetDetails<AccountOf<T>, BalanceOf<T>>, OptionQuery>;
+
+	#[pallet::storage]
+	// hands out the next free asset_number, incremented every time register_asset succeeds
+	#[pallet::getter(fn next_asset_id)]
+	pub type NextAssetId<T> = StorageValue<_, u32, ValueQuery>;
+
+	#[pallet::storage]
+	// accounts entered into the current lottery round, cleared once award_prize picks a winner
+	#[pallet::getter(fn participants)]
+	pub type Participants<T> = StorageValue<_, Vec<AccountOf<T>>, ValueQuery>;
+
+	#[pallet::storage]
+	// running total of every account's reserved balance tracked by this pallet, kept in sync with each reserve/unreserve
+	#[pallet::getter(fn total_reserved)]
+	pub type TotalReserved<T> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+	// Values above this are rejected by set_value -- keeps the stored value comfortably away from u32::MAX
+	const MAX_VALUE: u32 = u32::MAX / 2;
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// An account's free balance can't cover the reserve/transfer being requested, or (in award_prize)
+		/// a participant's tracked reserve is smaller than the ticket price
+		InsufficientBalance,
+		/// No asset is registered under the given asset_number
+		AssetNotFound,
+		/// The caller is not the account that originally registered the asset
+		NotAssetOwner,
+		/// The value passed to set_value is larger than MAX_VALUE
+		ValueTooLarge,
+		/// The caller has already bought a ticket for the current lottery round
+		AlreadyEnteredLottery,
+		/// award_prize was called while Participants is empty
+		NoParticipants,
+		/// TotalReserved or the account's GetReservedBalance entry would overflow or underflow if this reserve/unreserve went ahead
+		TotalReservedOverflow,
+		/// clear_value was called by an account that holds no active ValueDeposit
+		NoActiveDeposit,
+		/// NextAssetId has run out of u32 values
+		NextAssetIdOverflow,
+	}
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
@@ -86,6 +165,47 @@ pub mod pallet {
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
 
+	// Internal helpers, not dispatchable -- kept out of the #[pallet::call] impl below
+	impl<T: Config> Pallet<T> {
+		// Bumps TotalReserved by `amount`, rolling back if the running total would overflow
+		fn increase_total_reserved(amount: BalanceOf<T>) -> DispatchResult {
+			TotalReserved::<T>::try_mutate(|total| -> DispatchResult {
+				*total = total.checked_add(&amount).ok_or(Error::<T>::TotalReservedOverflow)?;
+				Ok(())
+			})
+		}
+
+		// Shrinks TotalReserved by `amount`, rolling back if the running total would underflow
+		fn decrease_total_reserved(amount: BalanceOf<T>) -> DispatchResult {
+			TotalReserved::<T>::try_mutate(|total| -> DispatchResult {
+				*total = total.checked_sub(&amount).ok_or(Error::<T>::TotalReservedOverflow)?;
+				Ok(())
+			})
+		}
+
+		// Reserves `amount` from `who` and records it in GetReservedBalance/TotalReserved.
+		// GetReservedBalance only ever tracks reserves this pallet itself made, so it stays a
+		// faithful subset of `who`'s whole-chain reserved_balance even if other pallets also reserve funds
+		fn reserve_and_track(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			T::Currency::reserve(who, amount).map_err(|_| Error::<T>::InsufficientBalance)?;
+			GetReservedBalance::<T>::try_mutate(who, |balance| -> DispatchResult {
+				*balance = balance.checked_add(&amount).ok_or(Error::<T>::TotalReservedOverflow)?;
+				Ok(())
+			})?;
+			Self::increase_total_reserved(amount)
+		}
+
+		// Unreserves `amount` for `who` and removes it from GetReservedBalance/TotalReserved
+		fn unreserve_and_track(who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			T::Currency::unreserve(who, amount);
+			GetReservedBalance::<T>::try_mutate(who, |balance| -> DispatchResult {
+				*balance = balance.checked_sub(&amount).ok_or(Error::<T>::TotalReservedOverflow)?;
+				Ok(())
+			})?;
+			Self::decrease_total_reserved(amount)
+		}
+	}
+
 	// This used to be decl module! macro but now it is an attribute instead
 	#[pallet::call] //function call being made to the chain
 	impl<T: Config> Pallet<T> {
@@ -100,11 +220,22 @@ pub mod pallet {
 			// Ensure that the caller is a regular keypair account
 			// ensure_signed is a function coming from frame support, which ensures that this origin is of the type AccountId
 			let caller = ensure_signed(origin)?;
-			
-			//T is Config and we are calling Currency type from it and then reserved_balance fn from that
-			let reserve_balance_of_caller = T::Currency::reserved_balance(&caller.clone());
 
-			// Print a message	
+			// Reject anything that would leave GetValue sitting uncomfortably close to u32::MAX
+			ensure!(value <= MAX_VALUE, Error::<T>::ValueTooLarge);
+
+			// Storing a value only costs a deposit the first time -- an account that already has an
+			// active ValueDeposit keeps it instead of reserving a second one on every call. This is
+			// tracked in its own ValueDeposits map, not the shared GetReservedBalance aggregate, so an
+			// account that only ever registered an asset or bought a lottery ticket isn't mistaken for one
+			let deposit = T::ValueDeposit::get();
+			let already_deposited = ValueDeposits::<T>::contains_key(&caller);
+			if !already_deposited {
+				Self::reserve_and_track(&caller, deposit)?;
+				ValueDeposits::<T>::insert(&caller, deposit);
+			}
+
+			// Print a message
 			print("Hello World");
 			// Inspecting a variable as well
 			debug::info!("Request sent by: {:?}", caller);
@@ -113,11 +244,141 @@ pub mod pallet {
 			Self::deposit_event(Event::HelloValueStored(value.clone(), caller.clone()));
 			// calling GetValue type with the value set by the user
 			GetValue::<T>::put(value.clone());
-			// Putting reservable_balance into the storage map
-			GetReservedBalance::<T>::insert(caller.clone(), reserve_balance_of_caller.clone());
+			if !already_deposited {
+				Self::deposit_event(Event::ValueReserved(caller, deposit));
+			}
 
 			// Indicate that this call succeeded
 			Ok(().into())
 		}
+
+		/// Unreserve the caller's ValueDeposit and clear their ValueDeposits entry
+		#[pallet::weight(10_000)]
+		pub fn clear_value(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			let caller = ensure_signed(origin)?;
+
+			// take() both confirms the caller actually holds a ValueDeposit and removes it so a
+			// second clear_value call can't siphon more funds out of an unrelated asset/ticket reserve
+			let deposit = ValueDeposits::<T>::take(&caller).ok_or(Error::<T>::NoActiveDeposit)?;
+			Self::unreserve_and_track(&caller, deposit)?;
+			GetValue::<T>::kill();
+
+			Self::deposit_event(Event::ValueCleared(caller));
+
+			Ok(().into())
+		}
+
+		/// Move `amount` out of the caller's free balance and into `dest`'s free balance
+		/// Unlike set_value/clear_value above, which only reserve and unreserve the caller's own funds, this moves funds between accounts
+		#[pallet::weight(10_000)]
+		pub fn transfer(origin: OriginFor<T>, dest: T::AccountId, amount: BalanceOf<T>) -> DispatchResultWithPostInfo {
+			let caller = ensure_signed(origin)?;
+
+			// KeepAlive makes sure the transfer can't reap the sender's account
+			T::Currency::transfer(&caller, &dest, amount, ExistenceRequirement::KeepAlive)
+				.map_err(|_| Error::<T>::InsufficientBalance)?;
+
+			Self::deposit_event(Event::Transferred(caller, dest, amount));
+
+			Ok(().into())
+		}
+
+		/// Reserve `cost` from the caller as a listing deposit and add `name` to the asset registry
+		#[pallet::weight(10_000)]
+		pub fn register_asset(origin: OriginFor<T>, name: Vec<u8>, cost: BalanceOf<T>) -> DispatchResultWithPostInfo {
+			let caller = ensure_signed(origin)?;
+
+			// Reserving the deposit first means a failed reserve never leaves a dangling registry entry
+			Self::reserve_and_track(&caller, cost.clone())?;
+
+			let asset_number = NextAssetId::<T>::get();
+			let asset_details = AssetDetails {
+				asset_name: name,
+				asset_number,
+				asset_cost: cost,
+				asset_owner: caller.clone(),
+			};
+
+			let next_asset_number = asset_number.checked_add(1).ok_or(Error::<T>::NextAssetIdOverflow)?;
+			AssetRegistry::<T>::insert(asset_number, asset_details);
+			NextAssetId::<T>::put(next_asset_number);
+
+			Self::deposit_event(Event::AssetRegistered(asset_number, caller));
+
+			Ok(().into())
+		}
+
+		/// Unreserve the listing deposit and remove `asset_number` from the registry
+		/// Only the account that originally called register_asset may do this
+		#[pallet::weight(10_000)]
+		pub fn deregister_asset(origin: OriginFor<T>, asset_number: u32) -> DispatchResultWithPostInfo {
+			let caller = ensure_signed(origin)?;
+
+			let asset_details = AssetRegistry::<T>::get(asset_number).ok_or(Error::<T>::AssetNotFound)?;
+			ensure!(asset_details.asset_owner == caller, Error::<T>::NotAssetOwner);
+
+			Self::unreserve_and_track(&caller, asset_details.asset_cost)?;
+			AssetRegistry::<T>::remove(asset_number);
+
+			Self::deposit_event(Event::AssetDeregistered(asset_number, caller));
+
+			Ok(().into())
+		}
+
+		/// Reserve the TicketPrice from the caller and enter them into the current lottery round
+		#[pallet::weight(10_000)]
+		pub fn buy_ticket(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			let caller = ensure_signed(origin)?;
+
+			let mut participants = Participants::<T>::get();
+			ensure!(!participants.contains(&caller), Error::<T>::AlreadyEnteredLottery);
+
+			Self::reserve_and_track(&caller, T::TicketPrice::get())?;
+			participants.push(caller.clone());
+			Participants::<T>::put(participants);
+
+			Self::deposit_event(Event::TicketBought(caller));
+
+			Ok(().into())
+		}
+
+		/// Pick a winner at random, unreserve every ticket, and pay the pooled total to the winner
+		#[pallet::weight(10_000)]
+		pub fn award_prize(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+
+			let participants = Participants::<T>::get();
+			ensure!(!participants.is_empty(), Error::<T>::NoParticipants);
+
+			let ticket_price = T::TicketPrice::get();
+			// Check every participant's ticket can actually be unreserved before this call writes
+			// any storage -- this FRAME generation doesn't roll back storage on a failed dispatch,
+			// so a fallible op partway through the payout loop below would leave a corrupt half-run
+			for participant in participants.iter() {
+				ensure!(GetReservedBalance::<T>::get(participant) >= ticket_price, Error::<T>::InsufficientBalance);
+			}
+
+			// Read the random hash for this round and treat its bytes as a big-endian integer
+			let (random_seed, _) = T::Randomness::random(b"lottery");
+			let random_number = U256::from_big_endian(random_seed.as_ref());
+			// Keep the full 256-bit value in the modulo instead of narrowing to its low bits first
+			let winner_index = (random_number % U256::from(participants.len() as u64)).as_usize();
+			let winner = participants[winner_index].clone();
+
+			let prize = ticket_price.saturating_mul((participants.len() as u32).into());
+
+			for participant in participants.iter() {
+				Self::unreserve_and_track(participant, ticket_price)?;
+				if participant != &winner {
+					T::Currency::transfer(participant, &winner, ticket_price, ExistenceRequirement::AllowDeath)
+						.map_err(|_| Error::<T>::InsufficientBalance)?;
+				}
+			}
+
+			Participants::<T>::kill();
+			Self::deposit_event(Event::PrizeAwarded(winner, prize));
+
+			Ok(().into())
+		}
 	}
 }
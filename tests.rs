@@ -0,0 +1,205 @@
+// Mock runtime + behavior tests for this pallet
+
+use crate as pallet_first_substrate_pallet;
+use crate::{Error, Event};
+use frame_support::{assert_noop, assert_ok, parameter_types};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Module, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Module, Call, Storage},
+		TemplateModule: pallet_first_substrate_pallet::{Module, Call, Storage, Event<T>},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const ValueDeposit: u64 = 10;
+	pub const TicketPrice: u64 = 5;
+}
+
+impl pallet_first_substrate_pallet::Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type ValueDeposit = ValueDeposit;
+	type TicketPrice = TicketPrice;
+	type Randomness = RandomnessCollectiveFlip;
+}
+
+fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default()
+		.build_storage::<Test>()
+		.unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (3, 100)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	t.into()
+}
+
+#[test]
+fn transfer_moves_funds_between_accounts() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::transfer(Origin::signed(1), 2, 30));
+		assert_eq!(Balances::free_balance(1), 70);
+		assert_eq!(Balances::free_balance(2), 130);
+	});
+}
+
+#[test]
+fn set_value_then_clear_value_round_trips_the_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::set_value(Origin::signed(1), 42));
+		assert_eq!(Balances::reserved_balance(1), ValueDeposit::get());
+		assert_eq!(TemplateModule::get_value(), 42);
+
+		// A second set_value from the same account must not reserve a second deposit
+		assert_ok!(TemplateModule::set_value(Origin::signed(1), 43));
+		assert_eq!(Balances::reserved_balance(1), ValueDeposit::get());
+
+		assert_ok!(TemplateModule::clear_value(Origin::signed(1)));
+		assert_eq!(Balances::reserved_balance(1), 0);
+
+		assert_noop!(
+			TemplateModule::clear_value(Origin::signed(1)),
+			Error::<Test>::NoActiveDeposit
+		);
+	});
+}
+
+#[test]
+fn clear_value_does_not_touch_an_unrelated_asset_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::register_asset(Origin::signed(1), b"widget".to_vec(), 20));
+
+		// account 1 never called set_value, so it has no ValueDeposit to clear
+		assert_noop!(
+			TemplateModule::clear_value(Origin::signed(1)),
+			Error::<Test>::NoActiveDeposit
+		);
+		assert_eq!(Balances::reserved_balance(1), 20);
+
+		assert_ok!(TemplateModule::deregister_asset(Origin::signed(1), 0));
+		assert_eq!(Balances::reserved_balance(1), 0);
+	});
+}
+
+#[test]
+fn only_the_asset_owner_can_deregister_it() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::register_asset(Origin::signed(1), b"widget".to_vec(), 20));
+
+		assert_noop!(
+			TemplateModule::deregister_asset(Origin::signed(2), 0),
+			Error::<Test>::NotAssetOwner
+		);
+		assert_noop!(
+			TemplateModule::deregister_asset(Origin::signed(1), 1),
+			Error::<Test>::AssetNotFound
+		);
+	});
+}
+
+#[test]
+fn award_prize_pays_the_pooled_tickets_to_one_participant() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::buy_ticket(Origin::signed(1)));
+		assert_ok!(TemplateModule::buy_ticket(Origin::signed(2)));
+		assert_ok!(TemplateModule::buy_ticket(Origin::signed(3)));
+
+		let total_before =
+			Balances::free_balance(1) + Balances::free_balance(2) + Balances::free_balance(3);
+
+		assert_ok!(TemplateModule::award_prize(Origin::signed(1)));
+
+		let total_after =
+			Balances::free_balance(1) + Balances::free_balance(2) + Balances::free_balance(3);
+		// the three unreserved tickets land back in free balance before being paid out
+		assert_eq!(total_after, total_before + 3 * TicketPrice::get());
+
+		// exactly one participant holds the whole pool; the other two are each one ticket down
+		let mut free = [Balances::free_balance(1), Balances::free_balance(2), Balances::free_balance(3)];
+		free.sort();
+		assert_eq!(free, [100 - TicketPrice::get(), 100 - TicketPrice::get(), 100 + 2 * TicketPrice::get()]);
+		assert!(TemplateModule::participants().is_empty());
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(Balances::reserved_balance(3), 0);
+	});
+}
+
+#[test]
+fn total_reserved_tracks_every_outstanding_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(TemplateModule::set_value(Origin::signed(1), 1));
+		assert_ok!(TemplateModule::register_asset(Origin::signed(2), b"widget".to_vec(), 20));
+		assert_ok!(TemplateModule::buy_ticket(Origin::signed(3)));
+
+		assert_eq!(
+			TemplateModule::total_reserved(),
+			ValueDeposit::get() + 20 + TicketPrice::get()
+		);
+
+		assert_ok!(TemplateModule::clear_value(Origin::signed(1)));
+		assert_ok!(TemplateModule::deregister_asset(Origin::signed(2), 0));
+
+		assert_eq!(TemplateModule::total_reserved(), TicketPrice::get());
+	});
+}